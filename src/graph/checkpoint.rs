@@ -0,0 +1,219 @@
+use std::{cell::RefCell, collections::HashSet};
+
+use crate::graph::node::{Node, NodeId, Zeros};
+use crate::graph::tape::{RecordedOpsRef, Recordable};
+
+//how a node's forward value is retained between forward and backward.
+//selectable per-graph so memory-heavy graphs (deep nets) can trade
+//recompute time for memory without changing anything else about how the
+//graph is built.
+pub trait CheckpointStrategy: std::fmt::Debug + Default {
+    fn should_retain(&self, node: &NodeId) -> bool;
+}
+
+//today's behaviour: every intermediate value sticks around for the whole
+//backward pass.
+#[derive(Debug, Default, Clone)]
+pub struct NoCheckpointing;
+
+impl CheckpointStrategy for NoCheckpointing {
+    fn should_retain(&self, _node: &NodeId) -> bool {
+        true
+    }
+}
+
+//only explicitly marked nodes keep their value; everything else gets
+//dropped after forward and rebuilt from the nearest stored ancestor when
+//backward actually needs it.
+#[derive(Debug, Default, Clone)]
+pub struct BalancedCheckpointing {
+    checkpoints: HashSet<NodeId>,
+}
+
+impl BalancedCheckpointing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn checkpoint(&mut self, node: NodeId) {
+        self.checkpoints.insert(node);
+    }
+}
+
+impl CheckpointStrategy for BalancedCheckpointing {
+    fn should_retain(&self, node: &NodeId) -> bool {
+        self.checkpoints.contains(node)
+    }
+}
+
+//a node whose value may have been released to save memory. holds enough to
+//recompute it on demand: the forward closure that reproduces `out` from
+//whatever parent nodes it closed over, plus those parents themselves so it
+//can still sit on the tape like any other op-node. `recompute` runs once in
+//`new` under every strategy; `NoCheckpointing` always retains that value, so
+//it never runs again under it - re-runs only happen under
+//`BalancedCheckpointing` for nodes that weren't marked to be kept.
+//this is what `graph::ops::record_add_checkpointed` builds in place of the
+//plain `RootNode` a non-checkpointed op produces.
+pub struct CheckpointedNode<Out, C> {
+    id: NodeId,
+    value: RefCell<Option<Out>>,
+    grad: RefCell<Option<Out>>,
+    recompute: Box<dyn Fn() -> Out>,
+    parents: Vec<RecordedOpsRef>,
+    strategy: C,
+}
+
+impl<Out: Clone, C: CheckpointStrategy> CheckpointedNode<Out, C> {
+    pub fn new(
+        recompute: impl Fn() -> Out + 'static,
+        parents: Vec<RecordedOpsRef>,
+        strategy: C,
+    ) -> Self {
+        let id = NodeId::new();
+        let value = recompute();
+        let retained = if strategy.should_retain(&id) {
+            Some(value)
+        } else {
+            None
+        };
+
+        Self {
+            id,
+            value: RefCell::new(retained),
+            grad: RefCell::new(None),
+            recompute: Box::new(recompute),
+            parents,
+            strategy,
+        }
+    }
+
+    pub fn id(&self) -> NodeId {
+        self.id.clone()
+    }
+
+    //returns the value, recomputing from the stored closure if it was
+    //released, then releasing it again afterward if the strategy says not
+    //to retain it - so memory doesn't creep back up over a long backward
+    //pass that touches this node more than once.
+    pub fn value(&self) -> Out {
+        if let Some(value) = self.value.borrow().as_ref() {
+            return value.clone();
+        }
+
+        let value = (self.recompute)();
+
+        if self.strategy.should_retain(&self.id) {
+            *self.value.borrow_mut() = Some(value.clone());
+        }
+
+        value
+    }
+}
+
+impl<Out, C> std::fmt::Debug for CheckpointedNode<Out, C>
+where
+    Out: std::fmt::Debug,
+    C: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CheckpointedNode")
+            .field("id", &self.id)
+            .field("strategy", &self.strategy)
+            .finish()
+    }
+}
+
+//a checkpointed node is itself an op in the graph - it walks to whatever
+//parents its recompute closure closed over, same as BinaryOpsNode/
+//SingleOpsNode do for theirs
+impl<Out, C> Recordable for CheckpointedNode<Out, C>
+where
+    Out: std::fmt::Debug,
+    C: std::fmt::Debug,
+{
+    fn id(&self) -> NodeId {
+        self.id.clone()
+    }
+
+    fn parents_ops(&self) -> Vec<RecordedOpsRef> {
+        self.parents.clone()
+    }
+}
+
+//mirrors RootNode's Node<Out> impl, except `value()` goes through the
+//recompute/release logic above instead of just cloning a field
+impl<Out, C> Node<Out> for CheckpointedNode<Out, C>
+where
+    Out: Zeros<Out> + Clone + std::ops::Add<Output = Out> + std::fmt::Debug,
+    C: CheckpointStrategy,
+{
+    fn id(&self) -> NodeId {
+        self.id.clone()
+    }
+
+    fn grad(&mut self) -> Out {
+        let grad_self: Out = match &*self.grad.borrow() {
+            Some(val) => val.clone(),
+            None => self.value().zeros(),
+        };
+        *self.grad.borrow_mut() = Some(grad_self.clone());
+        grad_self
+    }
+
+    fn value(&self) -> Out {
+        CheckpointedNode::value(self)
+    }
+
+    fn update_grad(&mut self, grad: Out) {
+        let updated = self.grad() + grad;
+        *self.grad.borrow_mut() = Some(updated);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn no_checkpointing_never_recomputes() {
+        let recompute_count = Rc::new(Cell::new(0));
+        let counted = recompute_count.clone();
+
+        let node = CheckpointedNode::new(move || {
+            counted.set(counted.get() + 1);
+            5
+        }, Vec::new(), NoCheckpointing);
+
+        node.value();
+        node.value();
+
+        // NoCheckpointing retains after the first run, so later reads
+        // should be cache hits, not re-invocations of recompute
+        assert_eq!(recompute_count.get(), 1);
+    }
+
+    #[test]
+    fn balanced_checkpointing_recomputes_unmarked_nodes() {
+        let recompute_count = Rc::new(Cell::new(0));
+        let counted = recompute_count.clone();
+
+        let node = CheckpointedNode::new(
+            move || {
+                counted.set(counted.get() + 1);
+                5
+            },
+            Vec::new(),
+            BalancedCheckpointing::new(),
+        );
+
+        // this node was never marked as a checkpoint, so every read past
+        // the first (which ran during ::new) has to recompute
+        node.value();
+        node.value();
+
+        assert_eq!(recompute_count.get(), 3);
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+
+use crate::graph::node::NodeId;
+use crate::Shape;
+
+//the op kind alone, with no operand/shape payload - this is what fusion
+//plans get cached by, since two chains with the same shape of ops (e.g.
+//mul then add then neg) compile to the same plan regardless of which
+//tensors they're actually run on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OpKind {
+    Add,
+    Mul,
+    Neg,
+}
+
+impl OpKind {
+    //how many tensor operands this op pulls off the front of the run -
+    //elementwise binary ops consume two, unary ops consume one
+    fn arity(self) -> usize {
+        match self {
+            OpKind::Add | OpKind::Mul => 2,
+            OpKind::Neg => 1,
+        }
+    }
+}
+
+//a recorded tensor op. GraphTensor's add/mul/neg still compute their result
+//eagerly through the node graph when called, and additionally append one of
+//these to the tensor's LazyGraph; execution is not deferred. a fusion pass
+//later scans contiguous elementwise runs and compiles them into one pass
+//over the underlying buffers, so replaying `(a * b + c).neg()` through it
+//doesn't allocate two intermediate tensors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TensorOpsDescription<const D: usize> {
+    Add {
+        lhs: NodeId,
+        rhs: NodeId,
+        out_shape: Shape<D>,
+    },
+    Mul {
+        lhs: NodeId,
+        rhs: NodeId,
+        out_shape: Shape<D>,
+    },
+    Neg {
+        input: NodeId,
+        out_shape: Shape<D>,
+    },
+}
+
+impl<const D: usize> TensorOpsDescription<D> {
+    pub fn kind(&self) -> OpKind {
+        match self {
+            TensorOpsDescription::Add { .. } => OpKind::Add,
+            TensorOpsDescription::Mul { .. } => OpKind::Mul,
+            TensorOpsDescription::Neg { .. } => OpKind::Neg,
+        }
+    }
+
+    pub fn out_shape(&self) -> &Shape<D> {
+        match self {
+            TensorOpsDescription::Add { out_shape, .. }
+            | TensorOpsDescription::Mul { out_shape, .. }
+            | TensorOpsDescription::Neg { out_shape, .. } => out_shape,
+        }
+    }
+
+    //every variant today is elementwise (add/mul/neg), so this is always
+    //true for now - it exists so a later non-elementwise description
+    //(reductions, matmul) has somewhere to opt out of fusion
+    fn is_elementwise(&self) -> bool {
+        true
+    }
+}
+
+//the per-client graph of not-yet-executed ops
+#[derive(Debug, Default)]
+pub struct LazyGraph<const D: usize> {
+    descriptions: Vec<TensorOpsDescription<D>>,
+}
+
+impl<const D: usize> LazyGraph<D> {
+    pub fn new() -> Self {
+        Self {
+            descriptions: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, description: TensorOpsDescription<D>) {
+        self.descriptions.push(description);
+    }
+
+    pub fn descriptions(&self) -> &[TensorOpsDescription<D>] {
+        &self.descriptions
+    }
+
+    //scans for the longest contiguous runs of elementwise descriptions and
+    //returns one fusion plan per run (runs of length 1 still get a plan -
+    //fusing a single op is just running it), going through `cache` so a
+    //run whose op-kind sequence has already been compiled gets reused
+    //instead of re-analyzed
+    pub fn fuse(&self, cache: &mut FusionCache) -> Vec<FusedPlan> {
+        let mut plans = Vec::new();
+        let mut run: Vec<OpKind> = Vec::new();
+
+        for description in &self.descriptions {
+            if description.is_elementwise() {
+                run.push(description.kind());
+            } else if !run.is_empty() {
+                plans.push(cache.get_or_compile(std::mem::take(&mut run)).clone());
+            }
+        }
+
+        if !run.is_empty() {
+            plans.push(cache.get_or_compile(run).clone());
+        }
+
+        plans
+    }
+}
+
+//a compiled fusion plan - just the ordered op kinds in the run. executing
+//one folds left over a flat operand list, applying each kind in turn
+//without materializing an intermediate Data between steps.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FusedPlan {
+    kinds: Vec<OpKind>,
+    operand_count: usize,
+}
+
+impl FusedPlan {
+    //the actual "compilation" step: walks the op sequence once to work out
+    //how many raw operand buffers a run through `execute` will need. this
+    //is the part FusionCache caches - once per distinct op-kind sequence,
+    //rather than recomputing operand_count on every call.
+    fn compile(kinds: Vec<OpKind>) -> Self {
+        let operand_count = kinds
+            .iter()
+            .enumerate()
+            .map(|(i, kind)| if i == 0 { kind.arity() } else { kind.arity() - 1 })
+            .sum();
+
+        Self { kinds, operand_count }
+    }
+
+    pub fn kinds(&self) -> &[OpKind] {
+        &self.kinds
+    }
+
+    //first op pulls its full arity off the operand list; every op after
+    //that only needs its remaining operands, since one of them is always
+    //the running result from the previous step
+    pub fn operand_count(&self) -> usize {
+        self.operand_count
+    }
+
+    //executes this plan in one pass over `value`, the flattened buffers of
+    //every operand the run consumes, in the order the descriptions were
+    //recorded. the first op takes its operands off the front; every op
+    //after that takes its own operands minus the one that's already the
+    //running result (one for add/mul, none for neg).
+    pub fn execute<P>(&self, value: &mut Vec<Vec<P>>) -> Vec<P>
+    where
+        P: Copy + std::ops::Add<Output = P> + std::ops::Mul<Output = P> + std::ops::Neg<Output = P>,
+    {
+        assert_eq!(
+            value.len(),
+            self.operand_count(),
+            "fused plan expects {} operands, got {}",
+            self.operand_count(),
+            value.len()
+        );
+
+        let mut operands = value.drain(..);
+        let mut acc: Vec<P> = operands
+            .next()
+            .expect("fused plan with no operands");
+
+        for kind in &self.kinds {
+            match kind {
+                OpKind::Neg => {
+                    acc = acc.into_iter().map(|v| -v).collect();
+                }
+                OpKind::Add => {
+                    let rhs = operands.next().expect("add op missing its rhs operand");
+                    acc = acc.into_iter().zip(rhs).map(|(a, b)| a + b).collect();
+                }
+                OpKind::Mul => {
+                    let rhs = operands.next().expect("mul op missing its rhs operand");
+                    acc = acc.into_iter().zip(rhs).map(|(a, b)| a * b).collect();
+                }
+            }
+        }
+
+        acc
+    }
+}
+
+//caches compiled plans keyed by the sequence of op-description kinds in a
+//run, so repeated forward passes over the same graph shape (same chain of
+//ops, different tensors) reuse the plan instead of re-analyzing it.
+//`hits`/`misses` exist so that reuse is actually observable - a cache that
+//can't report a hit rate could just as well not be a cache.
+#[derive(Debug, Default)]
+pub struct FusionCache {
+    plans: HashMap<Vec<OpKind>, FusedPlan>,
+    hits: usize,
+    misses: usize,
+}
+
+impl FusionCache {
+    pub fn new() -> Self {
+        Self {
+            plans: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    //looks up a compiled plan for this exact op-kind sequence. only the
+    //first time a given sequence is seen does it get compiled (analyzed
+    //into a FusedPlan, see FusedPlan::compile) - after that it's a plain
+    //hashmap lookup.
+    pub fn get_or_compile(&mut self, kinds: Vec<OpKind>) -> &FusedPlan {
+        if self.plans.contains_key(&kinds) {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+
+        self.plans
+            .entry(kinds.clone())
+            .or_insert_with(|| FusedPlan::compile(kinds))
+    }
+
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::node::NodeId;
+
+    #[test]
+    fn fuses_a_contiguous_elementwise_run_and_executes_it() {
+        let mut graph: LazyGraph<1> = LazyGraph::new();
+        let shape = crate::Shape::new([3]);
+
+        graph.push(TensorOpsDescription::Mul {
+            lhs: NodeId::new(),
+            rhs: NodeId::new(),
+            out_shape: shape.clone(),
+        });
+        graph.push(TensorOpsDescription::Add {
+            lhs: NodeId::new(),
+            rhs: NodeId::new(),
+            out_shape: shape.clone(),
+        });
+        graph.push(TensorOpsDescription::Neg {
+            input: NodeId::new(),
+            out_shape: shape,
+        });
+
+        let mut cache = FusionCache::new();
+        let plans = graph.fuse(&mut cache);
+
+        assert_eq!(plans.len(), 1);
+        let plan = &plans[0];
+        assert_eq!(plan.operand_count(), 3);
+
+        // -((a * b) + c) with a=[1,2,3], b=[4,5,6], c=[1,1,1]
+        let mut operands = vec![vec![1, 2, 3], vec![4, 5, 6], vec![1, 1, 1]];
+        let result = plan.execute(&mut operands);
+        assert_eq!(result, vec![-5, -11, -19]);
+    }
+
+    #[test]
+    fn fusion_cache_reuses_a_previously_compiled_plan() {
+        let mut cache = FusionCache::new();
+        let kinds = vec![OpKind::Mul, OpKind::Add];
+
+        cache.get_or_compile(kinds.clone());
+        cache.get_or_compile(kinds);
+
+        assert_eq!(cache.misses(), 1);
+        assert_eq!(cache.hits(), 1);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod checkpoint;
+pub mod lazy;
+pub mod node;
+pub mod ops;
+pub mod tape;
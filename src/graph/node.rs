@@ -1,8 +1,4 @@
-use std::{
-    cell:RefCell,
-    ops::{Add, Mul},
-    rc::Rc,
-};
+use std::{cell::RefCell, ops::Add, rc::Rc};
 
 //NodeId is the identity system, every node gets a unique id for tracking the graph
 #[derive(PartialEq, Eq, Debug, Clone, Hash)]
@@ -18,8 +14,17 @@ impl NodeId {
     }
 }
 
-//This trait is the contract, it defines what every node in the graph must be able to do
-pub trait Node<Out>: std::fmt::Debug {
+impl Default for NodeId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//This trait is the contract, it defines what every node in the graph must be able to do.
+//Recordable is a supertrait so any NodeRef<T> can be upcast straight into a
+//RecordedOpsRef when backward() walks the tape - no need to track a second,
+//type-erased handle alongside every NodeRef.
+pub trait Node<Out>: std::fmt::Debug + crate::graph::tape::Recordable {
     fn id(&self) -> NodeId;
     fn grad(&mut self) -> Out;
     fn value(&self) -> Out;
@@ -38,7 +43,7 @@ pub type NodeRef<T> = Rc<RefCell<dyn Node<T>>>;
 //any type implementing this trait knows how to create 0 filled version of type T
 //these are helper traits
 pub trait Zeros<T> {
-    fn zeroes(&self) -> T;
+    fn zeros(&self) -> T;
 }
 
 pub trait Ones<T> {
@@ -57,7 +62,7 @@ pub struct RootNode<Out> {
 impl <Out> RootNode<Out> {
     pub fn new(value: Out) -> Self {
         Self {
-            id: NodeId::new()
+            id: NodeId::new(),
             value,
             grad: None,
         }
@@ -66,7 +71,7 @@ impl <Out> RootNode<Out> {
 
 impl<Out> Node<Out> for RootNode<Out>
 where
-    Out: Zeroes<Out> + Clone + Mul<Output = Out> + Add<Output = Out>,
+    Out: Zeros<Out> + Clone + Add<Output = Out>,
     Out: std::fmt::Debug,
 {
     fn id(&self) -> NodeId {
@@ -93,6 +98,21 @@ where
 
 
 
+//root nodes are leaves - they have no parent ops, so recording a tape that
+//bottoms out at a RootNode just stops the walk there
+impl<Out> crate::graph::tape::Recordable for RootNode<Out>
+where
+    Out: std::fmt::Debug,
+{
+    fn id(&self) -> NodeId {
+        self.id.clone()
+    }
+
+    fn parents_ops(&self) -> Vec<crate::graph::tape::RecordedOpsRef> {
+        Vec::new()
+    }
+}
+
 //so how they would all work together is:
 
 //1. Create Nodes
@@ -0,0 +1,555 @@
+use std::ops::Mul;
+
+use crate::graph::node::{Node, NodeId, NodeRef};
+use crate::graph::tape::{RecordedOpsRef, Recordable};
+use crate::node_init;
+
+//everything a binary op's partial derivatives need: the left/right operands
+//and the node they produced. `partial_left`/`partial_right` read whichever
+//of these the chain rule calls for (e.g. mul's left partial is just rhs's
+//value).
+pub struct BinaryOpsState<Lhs, Rhs, Out> {
+    pub lhs: NodeRef<Lhs>,
+    pub rhs: NodeRef<Rhs>,
+    pub out: NodeRef<Out>,
+}
+
+//`out_grad` is threaded in explicitly (rather than being combined
+//generically by BinaryOpsNode::backward) because how a partial combines
+//with the upstream grad isn't the same for every op: elementwise ops just
+//multiply, but matmul's partials have to be multiplied in a specific order
+//and aren't even the same shape as `out_grad`.
+pub trait BinaryOps<Lhs, Rhs, Out> {
+    fn partial_left(&self, state: &BinaryOpsState<Lhs, Rhs, Out>, out_grad: &Out) -> Lhs;
+    fn partial_right(&self, state: &BinaryOpsState<Lhs, Rhs, Out>, out_grad: &Out) -> Rhs;
+}
+
+pub struct SingleOpsState<In, Out> {
+    pub input: NodeRef<In>,
+    pub out: NodeRef<Out>,
+}
+
+pub trait SingleOps<In, Out> {
+    fn partial(&self, state: &SingleOpsState<In, Out>, out_grad: &Out) -> In;
+}
+
+//the node node_init!'s `lhs/rhs/out` arm builds. `Op` is a zero-sized marker
+//(AddOp, MulOp, ...) carrying the partial-derivative rule for whichever
+//forward op created this node.
+#[derive(Debug)]
+pub struct BinaryOpsNode<Lhs, Rhs, Out, Op> {
+    id: NodeId,
+    lhs: NodeRef<Lhs>,
+    rhs: NodeRef<Rhs>,
+    out: NodeRef<Out>,
+    op: Op,
+}
+
+//what node_init!'s `lhs/rhs/out` arm actually hands back - named here so
+//record_* functions that pin a concrete Op don't have to spell out the
+//whole Rc<RefCell<...>> nesting themselves
+type BinaryOpsNodeRef<Lhs, Rhs, Out, Op> = std::rc::Rc<std::cell::RefCell<BinaryOpsNode<Lhs, Rhs, Out, Op>>>;
+
+impl<Lhs, Rhs, Out, Op: Default> BinaryOpsNode<Lhs, Rhs, Out, Op> {
+    pub fn new(lhs: NodeRef<Lhs>, rhs: NodeRef<Rhs>, out: NodeRef<Out>) -> Self {
+        Self {
+            id: NodeId::new(),
+            lhs,
+            rhs,
+            out,
+            op: Op::default(),
+        }
+    }
+
+    fn state(&self) -> BinaryOpsState<Lhs, Rhs, Out> {
+        BinaryOpsState {
+            lhs: self.lhs.clone(),
+            rhs: self.rhs.clone(),
+            out: self.out.clone(),
+        }
+    }
+}
+
+impl<Lhs, Rhs, Out, Op> BinaryOpsNode<Lhs, Rhs, Out, Op>
+where
+    Op: BinaryOps<Lhs, Rhs, Out> + Default,
+    Out: Clone,
+{
+    //reads the output node's accumulated grad, asks the op for each parent's
+    //contribution, and hands it off via update_grad - same accumulation
+    //RootNode already does, so grads from multiple consumers just add up.
+    pub fn backward(&mut self) {
+        let state = self.state();
+        let out_grad = self.out.borrow_mut().grad();
+
+        let grad_lhs = self.op.partial_left(&state, &out_grad);
+        self.lhs.borrow_mut().update_grad(grad_lhs);
+
+        let grad_rhs = self.op.partial_right(&state, &out_grad);
+        self.rhs.borrow_mut().update_grad(grad_rhs);
+    }
+}
+
+impl<Lhs, Rhs, Out, Op> Recordable for BinaryOpsNode<Lhs, Rhs, Out, Op>
+where
+    Lhs: std::fmt::Debug + 'static,
+    Rhs: std::fmt::Debug + 'static,
+    Out: std::fmt::Debug + Clone + 'static,
+    Op: std::fmt::Debug + BinaryOps<Lhs, Rhs, Out> + Default,
+{
+    fn id(&self) -> NodeId {
+        self.id.clone()
+    }
+
+    //both operands are themselves NodeRefs, which upcast straight into
+    //RecordedOpsRef since Node<T> carries Recordable as a supertrait
+    fn parents_ops(&self) -> Vec<RecordedOpsRef> {
+        vec![self.lhs.clone(), self.rhs.clone()]
+    }
+
+    //delegates to the inherent backward() above - named via UFCS so this
+    //doesn't recurse into itself
+    fn backward(&mut self) {
+        BinaryOpsNode::backward(self)
+    }
+}
+
+//an op-node mirrors its output node's Node<Out> interface, so it can be
+//handed to further ops as a NodeRef<Out> just like a RootNode can
+impl<Lhs, Rhs, Out, Op> Node<Out> for BinaryOpsNode<Lhs, Rhs, Out, Op>
+where
+    Lhs: std::fmt::Debug + 'static,
+    Rhs: std::fmt::Debug + 'static,
+    Out: std::fmt::Debug + Clone + 'static,
+    Op: std::fmt::Debug + BinaryOps<Lhs, Rhs, Out> + Default,
+{
+    fn id(&self) -> NodeId {
+        self.id.clone()
+    }
+
+    fn grad(&mut self) -> Out {
+        self.out.borrow_mut().grad()
+    }
+
+    fn value(&self) -> Out {
+        self.out.borrow().value()
+    }
+
+    fn update_grad(&mut self, grad: Out) {
+        self.out.borrow_mut().update_grad(grad)
+    }
+}
+
+//the node node_init!'s `input/out` arm builds - same idea as BinaryOpsNode
+//but for ops with a single operand (neg, reshape, activations, ...).
+#[derive(Debug)]
+pub struct SingleOpsNode<In, Out, Op> {
+    id: NodeId,
+    input: NodeRef<In>,
+    out: NodeRef<Out>,
+    op: Op,
+}
+
+impl<In, Out, Op: Default> SingleOpsNode<In, Out, Op> {
+    pub fn new(input: NodeRef<In>, out: NodeRef<Out>) -> Self {
+        Self {
+            id: NodeId::new(),
+            input,
+            out,
+            op: Op::default(),
+        }
+    }
+
+    fn state(&self) -> SingleOpsState<In, Out> {
+        SingleOpsState {
+            input: self.input.clone(),
+            out: self.out.clone(),
+        }
+    }
+}
+
+impl<In, Out, Op> SingleOpsNode<In, Out, Op>
+where
+    Op: SingleOps<In, Out> + Default,
+{
+    pub fn backward(&mut self) {
+        let state = self.state();
+        let out_grad = self.out.borrow_mut().grad();
+
+        let grad_in = self.op.partial(&state, &out_grad);
+        self.input.borrow_mut().update_grad(grad_in);
+    }
+}
+
+impl<In, Out, Op> Recordable for SingleOpsNode<In, Out, Op>
+where
+    In: std::fmt::Debug + 'static,
+    Out: std::fmt::Debug + 'static,
+    Op: std::fmt::Debug + SingleOps<In, Out> + Default,
+{
+    fn id(&self) -> NodeId {
+        self.id.clone()
+    }
+
+    fn parents_ops(&self) -> Vec<RecordedOpsRef> {
+        vec![self.input.clone()]
+    }
+
+    //delegates to the inherent backward() above - named via UFCS so this
+    //doesn't recurse into itself
+    fn backward(&mut self) {
+        SingleOpsNode::backward(self)
+    }
+}
+
+impl<In, Out, Op> Node<Out> for SingleOpsNode<In, Out, Op>
+where
+    In: std::fmt::Debug + 'static,
+    Out: std::fmt::Debug + 'static,
+    Op: std::fmt::Debug + SingleOps<In, Out> + Default,
+{
+    fn id(&self) -> NodeId {
+        self.id.clone()
+    }
+
+    fn grad(&mut self) -> Out {
+        self.out.borrow_mut().grad()
+    }
+
+    fn value(&self) -> Out {
+        self.out.borrow().value()
+    }
+
+    fn update_grad(&mut self, grad: Out) {
+        self.out.borrow_mut().update_grad(grad)
+    }
+}
+
+//--- concrete op markers, one per forward method in tensor::tensor ---
+
+//add: d(lhs+rhs)/d(lhs) = 1, d(lhs+rhs)/d(rhs) = 1 - the contribution is just
+//the upstream grad, unchanged
+#[derive(Debug, Default)]
+pub struct AddOp;
+
+impl<T: Clone> BinaryOps<T, T, T> for AddOp {
+    fn partial_left(&self, _state: &BinaryOpsState<T, T, T>, out_grad: &T) -> T {
+        out_grad.clone()
+    }
+
+    fn partial_right(&self, _state: &BinaryOpsState<T, T, T>, out_grad: &T) -> T {
+        out_grad.clone()
+    }
+}
+
+//mul: d(lhs*rhs)/d(lhs) = rhs, d(lhs*rhs)/d(rhs) = lhs, elementwise against
+//the upstream grad
+#[derive(Debug, Default)]
+pub struct MulOp;
+
+impl<T> BinaryOps<T, T, T> for MulOp
+where
+    T: Clone + Mul<Output = T>,
+{
+    fn partial_left(&self, state: &BinaryOpsState<T, T, T>, out_grad: &T) -> T {
+        state.rhs.borrow().value() * out_grad.clone()
+    }
+
+    fn partial_right(&self, state: &BinaryOpsState<T, T, T>, out_grad: &T) -> T {
+        state.lhs.borrow().value() * out_grad.clone()
+    }
+}
+
+//neg: d(-x)/dx = -1, so the contribution is just the negated upstream grad
+#[derive(Debug, Default)]
+pub struct NegOp;
+
+impl<T> SingleOps<T, T> for NegOp
+where
+    T: std::ops::Neg<Output = T> + Clone,
+{
+    fn partial(&self, _state: &SingleOpsState<T, T>, out_grad: &T) -> T {
+        -(out_grad.clone())
+    }
+}
+
+//matmul: d(lhs . rhs)/d(lhs) = out_grad . rhs^T, d(lhs . rhs)/d(rhs) = lhs^T . out_grad.
+//unlike add/mul these aren't elementwise and the multiplication order isn't
+//symmetric, which is exactly why BinaryOps takes out_grad directly instead
+//of a local-partial-times-grad shortcut.
+#[derive(Debug, Default)]
+pub struct MatmulOp;
+
+impl<P> BinaryOps<crate::Data<P, 2>, crate::Data<P, 2>, crate::Data<P, 2>> for MatmulOp
+where
+    P: Copy + Default + std::ops::Mul<Output = P> + std::ops::Add<Output = P> + std::fmt::Debug,
+{
+    fn partial_left(
+        &self,
+        state: &BinaryOpsState<crate::Data<P, 2>, crate::Data<P, 2>, crate::Data<P, 2>>,
+        out_grad: &crate::Data<P, 2>,
+    ) -> crate::Data<P, 2> {
+        out_grad
+            .matmul(&state.rhs.borrow().value().transpose())
+            .expect("matmul backward: shapes recorded during forward must still agree")
+    }
+
+    fn partial_right(
+        &self,
+        state: &BinaryOpsState<crate::Data<P, 2>, crate::Data<P, 2>, crate::Data<P, 2>>,
+        out_grad: &crate::Data<P, 2>,
+    ) -> crate::Data<P, 2> {
+        state
+            .lhs
+            .borrow()
+            .value()
+            .transpose()
+            .matmul(out_grad)
+            .expect("matmul backward: shapes recorded during forward must still agree")
+    }
+}
+
+//computes lhs . rhs and records a BinaryOpsNode<_, _, _, MatmulOp>. forward
+//validates shapes itself (via Data::matmul) so a bad pairing fails here
+//rather than surfacing as a confusing panic during backward.
+pub fn record_matmul<P>(
+    lhs: NodeRef<crate::Data<P, 2>>,
+    rhs: NodeRef<crate::Data<P, 2>>,
+) -> Result<NodeRef<crate::Data<P, 2>>, crate::tensor::tensor::TensorError>
+where
+    P: Copy + Default + std::ops::Mul<Output = P> + std::ops::Add<Output = P> + std::fmt::Debug + 'static,
+{
+    let out_value = lhs.borrow().value().matmul(&rhs.borrow().value())?;
+    let out = node_init!(root out_value);
+    let node: BinaryOpsNodeRef<crate::Data<P, 2>, crate::Data<P, 2>, crate::Data<P, 2>, MatmulOp> =
+        node_init!(lhs lhs, rhs rhs, out out,);
+    Ok(node)
+}
+
+//broadcasting variants of AddOp/MulOp: the incoming grad has the
+//broadcasted output shape, so each parent's raw contribution has to be
+//summed back down to its own (possibly smaller) shape via sum_to_shape
+//before it can be handed to update_grad - otherwise a stretched parent
+//would accumulate a gradient bigger than the tensor it owns.
+#[derive(Debug, Default)]
+pub struct BroadcastAddOp;
+
+impl<P, const D: usize> BinaryOps<crate::Data<P, D>, crate::Data<P, D>, crate::Data<P, D>> for BroadcastAddOp
+where
+    P: Copy + Default + std::ops::Add<Output = P>,
+{
+    fn partial_left(
+        &self,
+        state: &BinaryOpsState<crate::Data<P, D>, crate::Data<P, D>, crate::Data<P, D>>,
+        out_grad: &crate::Data<P, D>,
+    ) -> crate::Data<P, D> {
+        out_grad.sum_to_shape(&state.lhs.borrow().value().shape)
+    }
+
+    fn partial_right(
+        &self,
+        state: &BinaryOpsState<crate::Data<P, D>, crate::Data<P, D>, crate::Data<P, D>>,
+        out_grad: &crate::Data<P, D>,
+    ) -> crate::Data<P, D> {
+        out_grad.sum_to_shape(&state.rhs.borrow().value().shape)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct BroadcastMulOp;
+
+impl<P, const D: usize> BinaryOps<crate::Data<P, D>, crate::Data<P, D>, crate::Data<P, D>> for BroadcastMulOp
+where
+    P: Copy + Default + std::ops::Add<Output = P> + std::ops::Mul<Output = P>,
+{
+    fn partial_left(
+        &self,
+        state: &BinaryOpsState<crate::Data<P, D>, crate::Data<P, D>, crate::Data<P, D>>,
+        out_grad: &crate::Data<P, D>,
+    ) -> crate::Data<P, D> {
+        let rhs = state.rhs.borrow().value();
+        rhs.broadcast_mul(out_grad)
+            .expect("broadcast mul backward: shapes recorded during forward must still agree")
+            .sum_to_shape(&state.lhs.borrow().value().shape)
+    }
+
+    fn partial_right(
+        &self,
+        state: &BinaryOpsState<crate::Data<P, D>, crate::Data<P, D>, crate::Data<P, D>>,
+        out_grad: &crate::Data<P, D>,
+    ) -> crate::Data<P, D> {
+        let lhs = state.lhs.borrow().value();
+        lhs.broadcast_mul(out_grad)
+            .expect("broadcast mul backward: shapes recorded during forward must still agree")
+            .sum_to_shape(&state.rhs.borrow().value().shape)
+    }
+}
+
+//computes lhs + rhs with broadcasting and records a
+//BinaryOpsNode<_, _, _, BroadcastAddOp>
+pub fn record_broadcast_add<P, const D: usize>(
+    lhs: NodeRef<crate::Data<P, D>>,
+    rhs: NodeRef<crate::Data<P, D>>,
+) -> Result<NodeRef<crate::Data<P, D>>, crate::tensor::tensor::TensorError>
+where
+    P: Copy + Default + std::ops::Add<Output = P> + std::fmt::Debug + 'static,
+{
+    let out_value = lhs.borrow().value().broadcast_add(&rhs.borrow().value())?;
+    let out = node_init!(root out_value);
+    let node: BinaryOpsNodeRef<crate::Data<P, D>, crate::Data<P, D>, crate::Data<P, D>, BroadcastAddOp> =
+        node_init!(lhs lhs, rhs rhs, out out,);
+    Ok(node)
+}
+
+//computes lhs * rhs with broadcasting and records a
+//BinaryOpsNode<_, _, _, BroadcastMulOp>
+pub fn record_broadcast_mul<P, const D: usize>(
+    lhs: NodeRef<crate::Data<P, D>>,
+    rhs: NodeRef<crate::Data<P, D>>,
+) -> Result<NodeRef<crate::Data<P, D>>, crate::tensor::tensor::TensorError>
+where
+    P: Copy + Default + std::ops::Add<Output = P> + std::ops::Mul<Output = P> + std::fmt::Debug + 'static,
+{
+    let out_value = lhs.borrow().value().broadcast_mul(&rhs.borrow().value())?;
+    let out = node_init!(root out_value);
+    let node: BinaryOpsNodeRef<crate::Data<P, D>, crate::Data<P, D>, crate::Data<P, D>, BroadcastMulOp> =
+        node_init!(lhs lhs, rhs rhs, out out,);
+    Ok(node)
+}
+
+//--- forward wiring, called from TensorOpsAdd/TensorOpsMul/TensorOpsNeg impls ---
+
+//computes lhs + rhs and records a BinaryOpsNode<_, _, _, AddOp> for it, so
+//the result participates in backprop exactly like any other node
+pub fn record_add<T>(lhs: NodeRef<T>, rhs: NodeRef<T>) -> NodeRef<T>
+where
+    T: std::ops::Add<Output = T> + crate::graph::node::Zeros<T> + Clone + std::fmt::Debug + 'static,
+{
+    let out_value = lhs.borrow().value() + rhs.borrow().value();
+    let out = node_init!(root out_value);
+    let node: BinaryOpsNodeRef<T, T, T, AddOp> =
+        node_init!(lhs lhs, rhs rhs, out out,);
+    node
+}
+
+//same as record_add, except the output node is a CheckpointedNode instead
+//of a plain RootNode: under a strategy that doesn't retain it, `out`'s
+//value gets recomputed from lhs/rhs on demand during backward instead of
+//sitting in memory for the whole pass. forward still produces the value up
+//front (CheckpointedNode::new runs `recompute` once) so the return type and
+//behaviour match record_add exactly - only the retention policy differs.
+pub fn record_add_checkpointed<T, C>(lhs: NodeRef<T>, rhs: NodeRef<T>, strategy: C) -> NodeRef<T>
+where
+    T: std::ops::Add<Output = T> + crate::graph::node::Zeros<T> + Clone + std::fmt::Debug + 'static,
+    C: crate::graph::checkpoint::CheckpointStrategy + 'static,
+{
+    let recompute_lhs = lhs.clone();
+    let recompute_rhs = rhs.clone();
+    let recompute = move || recompute_lhs.borrow().value() + recompute_rhs.borrow().value();
+    let parents: Vec<RecordedOpsRef> = vec![lhs.clone(), rhs.clone()];
+
+    let out = std::rc::Rc::new(std::cell::RefCell::new(crate::graph::checkpoint::CheckpointedNode::new(
+        recompute, parents, strategy,
+    )));
+    let node: BinaryOpsNodeRef<T, T, T, AddOp> =
+        node_init!(lhs lhs, rhs rhs, out out,);
+    node
+}
+
+//computes lhs * rhs and records a BinaryOpsNode<_, _, _, MulOp>
+pub fn record_mul<T>(lhs: NodeRef<T>, rhs: NodeRef<T>) -> NodeRef<T>
+where
+    T: std::ops::Mul<Output = T>
+        + std::ops::Add<Output = T>
+        + crate::graph::node::Zeros<T>
+        + Clone
+        + std::fmt::Debug
+        + 'static,
+{
+    let out_value = lhs.borrow().value() * rhs.borrow().value();
+    let out = node_init!(root out_value);
+    let node: BinaryOpsNodeRef<T, T, T, MulOp> =
+        node_init!(lhs lhs, rhs rhs, out out,);
+    node
+}
+
+//computes -input and records a SingleOpsNode<_, _, NegOp>
+pub fn record_neg<T>(input: NodeRef<T>) -> NodeRef<T>
+where
+    T: std::ops::Neg<Output = T>
+        + std::ops::Add<Output = T>
+        + crate::graph::node::Zeros<T>
+        + Clone
+        + std::fmt::Debug
+        + 'static,
+{
+    let out_value = -input.borrow().value();
+    let out = node_init!(root out_value);
+    let node: std::rc::Rc<std::cell::RefCell<SingleOpsNode<T, T, NegOp>>> =
+        node_init!(input input, out out,);
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::tape;
+    use crate::{Data, Shape};
+
+    #[test]
+    fn gradient_accumulates_when_a_node_feeds_an_op_twice() {
+        let x_value = Data::new(vec![3], Shape::new([1]));
+        let x: NodeRef<Data<i32, 1>> = node_init!(root x_value);
+
+        // out = x + x, so both of AddOp's partials land back on the same
+        // node - its grad should be the sum of both contributions, not
+        // just the last one written
+        let out = record_add(x.clone(), x.clone());
+
+        tape::backward(out, Data::new(vec![1], Shape::new([1])));
+
+        assert_eq!(x.borrow_mut().grad().value, vec![2]);
+    }
+
+    #[test]
+    fn backward_propagates_through_more_than_one_op() {
+        let x_value = Data::new(vec![3], Shape::new([1]));
+        let x: NodeRef<Data<i32, 1>> = node_init!(root x_value);
+
+        // z = -(x + x), so dz/dx = -2 - this only comes out right if
+        // backward() visits z before y, and y before x, since each op
+        // needs the grad the op downstream of it just wrote
+        let y = record_add(x.clone(), x.clone());
+        let z = record_neg(y);
+
+        tape::backward(z, Data::new(vec![1], Shape::new([1])));
+
+        assert_eq!(x.borrow_mut().grad().value, vec![-2]);
+    }
+
+    #[test]
+    fn a_shared_non_leaf_ancestor_only_applies_its_gradient_once() {
+        let x_value = Data::new(vec![1], Shape::new([1]));
+        let y_value = Data::new(vec![1], Shape::new([1]));
+        let z_value = Data::new(vec![1], Shape::new([1]));
+        let u_value = Data::new(vec![1], Shape::new([1]));
+        let x: NodeRef<Data<i32, 1>> = node_init!(root x_value);
+        let y: NodeRef<Data<i32, 1>> = node_init!(root y_value);
+        let z: NodeRef<Data<i32, 1>> = node_init!(root z_value);
+        let u: NodeRef<Data<i32, 1>> = node_init!(root u_value);
+
+        // w = x + y feeds two different downstream ops (p and q), which are
+        // then combined into a single output - w is reachable from `out` via
+        // two separate paths. if w's own backward() fired once per path
+        // instead of once per node, x/y's gradient would be double-counted.
+        let w = record_add(x.clone(), y.clone());
+        let p = record_add(w.clone(), z);
+        let q = record_add(w, u);
+        let out = record_add(p, q);
+
+        tape::backward(out, Data::new(vec![1], Shape::new([1])));
+
+        assert_eq!(x.borrow_mut().grad().value, vec![2]);
+        assert_eq!(y.borrow_mut().grad().value, vec![2]);
+    }
+}
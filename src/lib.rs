@@ -0,0 +1,7 @@
+pub mod graph;
+pub mod tensor;
+
+pub use tensor::data::Data;
+pub use tensor::graph_tensor::GraphTensor;
+pub use tensor::shape::Shape;
+pub use tensor::tensor::*;
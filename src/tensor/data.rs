@@ -1,4 +1,6 @@
+use crate::graph::node::{Ones, Zeros};
 use crate::Shape;
+use derive_new::new;
 use rand::{distributions::Standard, prelude::Distribution};
 
 #[derive(new, Debug, Clone, PartialEq)]
@@ -26,8 +28,220 @@ where
     }
 }
 
-impl <P: std::fmt::Debug + Copy, const A: usize> From<[P; A] for Data<P, 1> {
+impl<P: std::fmt::Debug + Copy, const A: usize> From<[P; A]> for Data<P, 1> {
     fn from(elems: [P; A]) -> Self {
-        
+        Data::new(elems.to_vec(), Shape::new([A]))
     }
-}
\ No newline at end of file
+}
+
+//same-shape elementwise arithmetic - the non-broadcasting counterpart to
+//broadcast_add/broadcast_mul below, used by record_add/record_mul/record_neg
+//(graph::ops) to combine forward values and by RootNode's Zeros/Add
+//bound so a Data<P, D> can sit directly in the autodiff graph as a Node<Out>
+impl<P, const D: usize> std::ops::Add for Data<P, D>
+where
+    P: Copy + std::ops::Add<Output = P>,
+{
+    type Output = Data<P, D>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let value = self.value.iter().zip(rhs.value.iter()).map(|(a, b)| *a + *b).collect();
+        Data::new(value, self.shape)
+    }
+}
+
+impl<P, const D: usize> std::ops::Mul for Data<P, D>
+where
+    P: Copy + std::ops::Mul<Output = P>,
+{
+    type Output = Data<P, D>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let value = self.value.iter().zip(rhs.value.iter()).map(|(a, b)| *a * *b).collect();
+        Data::new(value, self.shape)
+    }
+}
+
+impl<P, const D: usize> std::ops::Neg for Data<P, D>
+where
+    P: Copy + std::ops::Neg<Output = P>,
+{
+    type Output = Data<P, D>;
+
+    fn neg(self) -> Self::Output {
+        let value = self.value.iter().map(|a| -*a).collect();
+        Data::new(value, self.shape)
+    }
+}
+
+impl<P: Copy + Default, const D: usize> Zeros<Data<P, D>> for Data<P, D> {
+    fn zeros(&self) -> Data<P, D> {
+        Data::new(vec![P::default(); self.shape.num_elements()], self.shape.clone())
+    }
+}
+
+impl<P: Copy + num_traits::One, const D: usize> Ones<Data<P, D>> for Data<P, D> {
+    fn ones(&self) -> Data<P, D> {
+        Data::new(vec![P::one(); self.shape.num_elements()], self.shape.clone())
+    }
+}
+
+impl<P: Copy, const D: usize> Data<P, D> {
+    //elementwise-combines `self` and `other`, broadcasting either operand's
+    //size-1 axes out to match the other (numpy/dfdx style). `combine` is
+    //applied once per output element, reading each operand through its own
+    //broadcast strides so a size-1 axis repeats instead of needing to be
+    //physically copied.
+    fn broadcast_zip(
+        &self,
+        other: &Data<P, D>,
+        combine: impl Fn(P, P) -> P,
+    ) -> Result<Data<P, D>, crate::tensor::tensor::TensorError> {
+        let out_shape = self.shape.broadcast(&other.shape)?;
+        let num_elements = out_shape.num_elements();
+        let mut value = Vec::with_capacity(num_elements);
+
+        for flat in 0..num_elements {
+            let idx = out_shape.unravel(flat);
+            let lhs = self.value[self.shape.ravel_broadcast(&idx)];
+            let rhs = other.value[other.shape.ravel_broadcast(&idx)];
+            value.push(combine(lhs, rhs));
+        }
+
+        Ok(Data::new(value, out_shape))
+    }
+
+    pub fn broadcast_add(&self, other: &Data<P, D>) -> Result<Data<P, D>, crate::tensor::tensor::TensorError>
+    where
+        P: std::ops::Add<Output = P>,
+    {
+        self.broadcast_zip(other, |a, b| a + b)
+    }
+
+    pub fn broadcast_mul(&self, other: &Data<P, D>) -> Result<Data<P, D>, crate::tensor::tensor::TensorError>
+    where
+        P: std::ops::Mul<Output = P>,
+    {
+        self.broadcast_zip(other, |a, b| a * b)
+    }
+
+    //the backward-side counterpart to broadcasting: sums a gradient shaped
+    //like a broadcasted output back down to `target`'s (possibly smaller)
+    //shape, so the parent that got stretched during forward receives a
+    //gradient matching the shape it actually owns.
+    pub fn sum_to_shape(&self, target: &Shape<D>) -> Data<P, D>
+    where
+        P: Default + std::ops::Add<Output = P>,
+    {
+        if &self.shape == target {
+            return self.clone();
+        }
+
+        let mut value = vec![P::default(); target.num_elements()];
+
+        for flat in 0..self.shape.num_elements() {
+            let idx = self.shape.unravel(flat);
+            let target_index = target.ravel_broadcast(&idx);
+            value[target_index] = value[target_index] + self.value[flat];
+        }
+
+        Data::new(value, target.clone())
+    }
+}
+
+//2-D only for now - matmul over higher-rank batches can build on this once
+//there's a concrete need for it
+impl<P> Data<P, 2>
+where
+    P: Copy + Default + std::ops::Mul<Output = P> + std::ops::Add<Output = P>,
+{
+    //swaps rows and cols. used directly, and by matmul's backward rule
+    //(grad_lhs = out_grad . rhs^T, grad_rhs = lhs^T . out_grad)
+    pub fn transpose(&self) -> Data<P, 2> {
+        let [rows, cols] = self.shape.dims;
+        let mut value = vec![P::default(); rows * cols];
+
+        for r in 0..rows {
+            for c in 0..cols {
+                value[c * rows + r] = self.value[r * cols + c];
+            }
+        }
+
+        Data::new(value, Shape::new([cols, rows]))
+    }
+
+    //standard row-by-column contraction: lhs is [m, k], rhs is [k, n], output
+    //is [m, n]. the shared k dimension has to agree or there's nothing
+    //sensible to multiply.
+    pub fn matmul(&self, other: &Data<P, 2>) -> Result<Data<P, 2>, crate::tensor::tensor::TensorError> {
+        let [m, k] = self.shape.dims;
+        let [k2, n] = other.shape.dims;
+
+        if k != k2 {
+            return Err(crate::tensor::tensor::TensorError::MatmulError(format!(
+                "matmul: lhs is [{}, {}] but rhs is [{}, {}] - inner dimensions must match",
+                m, k, k2, n
+            )));
+        }
+
+        let mut value = vec![P::default(); m * n];
+
+        for i in 0..m {
+            for j in 0..n {
+                let mut sum = P::default();
+                for p in 0..k {
+                    sum = sum + self.value[i * k + p] * other.value[p * n + j];
+                }
+                value[i * n + j] = sum;
+            }
+        }
+
+        Ok(Data::new(value, Shape::new([m, n])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matmul_contracts_rows_by_cols() {
+        // lhs: [[1, 2, 3], [4, 5, 6]] (2x3), rhs: [[7, 8], [9, 10], [11, 12]] (3x2)
+        let lhs = Data::new(vec![1, 2, 3, 4, 5, 6], Shape::new([2, 3]));
+        let rhs = Data::new(vec![7, 8, 9, 10, 11, 12], Shape::new([3, 2]));
+
+        let out = lhs.matmul(&rhs).expect("shapes agree");
+
+        assert_eq!(out.shape, Shape::new([2, 2]));
+        assert_eq!(out.value, vec![58, 64, 139, 154]);
+    }
+
+    #[test]
+    fn matmul_rejects_mismatched_inner_dimension() {
+        let lhs = Data::new(vec![1, 2, 3, 4], Shape::new([2, 2]));
+        let rhs = Data::new(vec![1, 2, 3], Shape::new([3, 1]));
+
+        let err = lhs.matmul(&rhs).expect_err("inner dims (2 vs 3) don't match");
+        assert!(matches!(err, crate::tensor::tensor::TensorError::MatmulError(_)));
+    }
+
+    #[test]
+    fn broadcast_stretches_size_one_axes() {
+        let lhs = Data::new(vec![1, 2, 3], Shape::new([1, 3]));
+        let rhs = Data::new(vec![10, 20], Shape::new([2, 1]));
+
+        let out = lhs.broadcast_add(&rhs).expect("size-1 axes broadcast");
+
+        assert_eq!(out.shape, Shape::new([2, 3]));
+        assert_eq!(out.value, vec![11, 12, 13, 21, 22, 23]);
+    }
+
+    #[test]
+    fn broadcast_rejects_incompatible_shapes() {
+        let lhs = Data::new(vec![1, 2, 3], Shape::new([1, 3]));
+        let rhs = Data::new(vec![1, 2], Shape::new([1, 2]));
+
+        let err = lhs.broadcast_add(&rhs).expect_err("3 vs 2 can't broadcast");
+        assert!(matches!(err, crate::tensor::tensor::TensorError::BroadcastError(_)));
+    }
+}
@@ -0,0 +1,311 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::graph::lazy::{FusionCache, LazyGraph, OpKind, TensorOpsDescription};
+use crate::graph::node::{Node, NodeRef};
+use crate::graph::ops::{record_add, record_broadcast_add, record_broadcast_mul, record_matmul, record_mul, record_neg};
+use crate::tensor::data::Data;
+use crate::tensor::shape::Shape;
+use crate::tensor::tensor::{TensorBase, TensorOpsAdd, TensorOpsMatmul, TensorOpsMul, TensorOpsNeg};
+
+//the concrete tensor type the TensorBase/TensorOps* traits (tensor::tensor)
+//are written against. wraps a NodeRef<Data<P, D>> so every forward op also
+//differentiates (see graph::ops::record_add and friends), and carries a
+//shared LazyGraph so tensor-tensor add/mul and neg additionally get
+//recorded for fusion (add_scalar/mul_scalar are not recorded there) -
+//the two graphs serve different purposes: the node graph is what backward()
+//walks, the lazy graph is what a later fused forward pass replays.
+#[derive(Clone)]
+pub struct GraphTensor<P, const D: usize> {
+    node: NodeRef<Data<P, D>>,
+    shape: Shape<D>,
+    lazy: Rc<RefCell<LazyGraph<D>>>,
+}
+
+//everything below needs a `Data<P, D>` that can actually sit in the graph
+//as a Node<Out> (see RootNode's bound in graph::node): P has to support
+//the plain elementwise arithmetic Data<P, D> forwards to (data.rs), plus
+//Default for Zeros and Debug for the graph's universal Debug bound.
+impl<P, const D: usize> GraphTensor<P, D>
+where
+    P: Copy + Default + std::fmt::Debug + std::ops::Add<Output = P> + std::ops::Mul<Output = P> + 'static,
+{
+    fn wrap(node: NodeRef<Data<P, D>>, shape: Shape<D>, lazy: Rc<RefCell<LazyGraph<D>>>) -> Self {
+        Self { node, shape, lazy }
+    }
+
+    fn fresh(node: NodeRef<Data<P, D>>, shape: Shape<D>) -> Self {
+        Self::wrap(node, shape, Rc::new(RefCell::new(LazyGraph::new())))
+    }
+
+    //an op's output shares the lazy graph of its left operand (`self`), so
+    //a description pushed while building `out` ends up on the same graph as
+    //the one `self` was pushed onto. tensors created via `from`/`empty`
+    //each start a fresh graph, and the right operand's graph is not merged
+    //in - descriptions recorded on it stay there.
+    pub fn lazy_graph(&self) -> Rc<RefCell<LazyGraph<D>>> {
+        self.lazy.clone()
+    }
+
+    //pushes a description of an elementwise binary op onto lhs's (`self`'s)
+    //lazy graph
+    fn push_binary(&self, rhs: &Self, kind: OpKind, out_shape: Shape<D>) {
+        let description = match kind {
+            OpKind::Add => TensorOpsDescription::Add {
+                lhs: Node::id(&*self.node.borrow()),
+                rhs: Node::id(&*rhs.node.borrow()),
+                out_shape,
+            },
+            OpKind::Mul => TensorOpsDescription::Mul {
+                lhs: Node::id(&*self.node.borrow()),
+                rhs: Node::id(&*rhs.node.borrow()),
+                out_shape,
+            },
+            OpKind::Neg => unreachable!("neg is pushed via push_unary"),
+        };
+        self.lazy.borrow_mut().push(description);
+    }
+
+    fn push_unary(&self, out_shape: Shape<D>) {
+        self.lazy.borrow_mut().push(TensorOpsDescription::Neg {
+            input: Node::id(&*self.node.borrow()),
+            out_shape,
+        });
+    }
+
+    //replays this tensor's accumulated elementwise history as one or more
+    //fused passes over `leaves` (the raw buffers of the root tensors the
+    //chain bottomed out at, in the order its ops were recorded), going
+    //through `cache` so repeated shapes of chains reuse their compiled
+    //plan. exists alongside the eager node graph as the batched/fused
+    //execution path - useful once the same chain of ops runs over many
+    //different tensors and re-walking the node graph per tensor would be
+    //wasteful.
+    pub fn run_fused(&self, leaves: Vec<Data<P, D>>, cache: &mut FusionCache) -> Vec<Data<P, D>>
+    where
+        P: std::ops::Neg<Output = P>,
+    {
+        let graph = self.lazy.borrow();
+        let plans = graph.fuse(cache);
+        let out_shape = self.shape.clone();
+
+        let mut buffers: Vec<Vec<P>> = leaves.into_iter().map(|data| data.value).collect();
+        let mut results = Vec::with_capacity(plans.len());
+
+        for plan in &plans {
+            let take = plan.operand_count().min(buffers.len());
+            let mut taken: Vec<Vec<P>> = buffers.drain(..take).collect();
+            results.push(Data::new(plan.execute(&mut taken), out_shape.clone()));
+        }
+
+        results
+    }
+}
+
+impl<P, const D: usize> TensorBase<P, D> for GraphTensor<P, D>
+where
+    P: Copy + Default + std::fmt::Debug + std::ops::Add<Output = P> + std::ops::Mul<Output = P> + 'static,
+{
+    fn shape(&self) -> &Shape<D> {
+        &self.shape
+    }
+
+    fn into_data(self) -> Data<P, D> {
+        self.node.borrow().value()
+    }
+
+    fn from<O: TensorBase<P, D>>(other: O) -> Self {
+        let shape = other.shape().clone();
+        let value = other.into_data();
+        let node: NodeRef<Data<P, D>> = crate::node_init!(root value);
+        Self::fresh(node, shape)
+    }
+
+    fn empty(shape: Shape<D>) -> Self {
+        let value = Data::new(vec![P::default(); shape.num_elements()], shape.clone());
+        let node: NodeRef<Data<P, D>> = crate::node_init!(root value);
+        Self::fresh(node, shape)
+    }
+}
+
+impl<P, const D: usize> std::ops::Add for GraphTensor<P, D>
+where
+    P: Copy + Default + std::fmt::Debug + std::ops::Add<Output = P> + std::ops::Mul<Output = P> + 'static,
+{
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        TensorOpsAdd::add(&self, &rhs)
+    }
+}
+
+impl<P, const D: usize> std::ops::Add<P> for GraphTensor<P, D>
+where
+    P: Copy + Default + std::fmt::Debug + std::ops::Add<Output = P> + std::ops::Mul<Output = P> + 'static,
+{
+    type Output = Self;
+
+    fn add(self, rhs: P) -> Self {
+        TensorOpsAdd::add_scalar(&self, &rhs)
+    }
+}
+
+impl<P, const D: usize> TensorOpsAdd<P, D> for GraphTensor<P, D>
+where
+    P: Copy + Default + std::fmt::Debug + std::ops::Add<Output = P> + std::ops::Mul<Output = P> + 'static,
+{
+    fn add(&self, other: &Self) -> Self {
+        let out_shape = self
+            .shape
+            .broadcast(&other.shape)
+            .unwrap_or_else(|err| panic!("GraphTensor::add: {:?}", err));
+        //same shape doesn't need sum_to_shape-ing the partials back down, so
+        //it stays on the plain (non-broadcasting) op
+        let node = if self.shape == other.shape {
+            record_add(self.node.clone(), other.node.clone())
+        } else {
+            record_broadcast_add(self.node.clone(), other.node.clone())
+                .unwrap_or_else(|err| panic!("GraphTensor::add: {:?}", err))
+        };
+        let out = Self::wrap(node, out_shape.clone(), self.lazy.clone());
+        self.push_binary(other, OpKind::Add, out_shape);
+        out
+    }
+
+    fn add_scalar(&self, other: &P) -> Self {
+        let scalar = Data::new(vec![*other; self.shape.num_elements()], self.shape.clone());
+        let scalar_node: NodeRef<Data<P, D>> = crate::node_init!(root scalar);
+        let node = record_add(self.node.clone(), scalar_node);
+        Self::wrap(node, self.shape.clone(), self.lazy.clone())
+    }
+}
+
+impl<P, const D: usize> std::ops::Mul for GraphTensor<P, D>
+where
+    P: Copy + Default + std::fmt::Debug + std::ops::Add<Output = P> + std::ops::Mul<Output = P> + 'static,
+{
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        TensorOpsMul::mul(&self, &rhs)
+    }
+}
+
+impl<P, const D: usize> std::ops::Mul<P> for GraphTensor<P, D>
+where
+    P: Copy + Default + std::fmt::Debug + std::ops::Add<Output = P> + std::ops::Mul<Output = P> + 'static,
+{
+    type Output = Self;
+
+    fn mul(self, rhs: P) -> Self {
+        TensorOpsMul::mul_scalar(&self, &rhs)
+    }
+}
+
+impl<P, const D: usize> TensorOpsMul<P, D> for GraphTensor<P, D>
+where
+    P: Copy + Default + std::fmt::Debug + std::ops::Add<Output = P> + std::ops::Mul<Output = P> + 'static,
+{
+    fn mul(&self, other: &Self) -> Self {
+        let out_shape = self
+            .shape
+            .broadcast(&other.shape)
+            .unwrap_or_else(|err| panic!("GraphTensor::mul: {:?}", err));
+        let node = if self.shape == other.shape {
+            record_mul(self.node.clone(), other.node.clone())
+        } else {
+            record_broadcast_mul(self.node.clone(), other.node.clone())
+                .unwrap_or_else(|err| panic!("GraphTensor::mul: {:?}", err))
+        };
+        let out = Self::wrap(node, out_shape.clone(), self.lazy.clone());
+        self.push_binary(other, OpKind::Mul, out_shape);
+        out
+    }
+
+    fn mul_scalar(&self, other: &P) -> Self {
+        let scalar = Data::new(vec![*other; self.shape.num_elements()], self.shape.clone());
+        let scalar_node: NodeRef<Data<P, D>> = crate::node_init!(root scalar);
+        let node = record_mul(self.node.clone(), scalar_node);
+        Self::wrap(node, self.shape.clone(), self.lazy.clone())
+    }
+}
+
+impl<P, const D: usize> std::ops::Neg for GraphTensor<P, D>
+where
+    P: Copy + Default + std::fmt::Debug + std::ops::Add<Output = P> + std::ops::Mul<Output = P> + std::ops::Neg<Output = P> + 'static,
+{
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        TensorOpsNeg::neg(&self)
+    }
+}
+
+impl<P, const D: usize> TensorOpsNeg<P, D> for GraphTensor<P, D>
+where
+    P: Copy + Default + std::fmt::Debug + std::ops::Add<Output = P> + std::ops::Mul<Output = P> + std::ops::Neg<Output = P> + 'static,
+{
+    fn neg(&self) -> Self {
+        let node = record_neg(self.node.clone());
+        let out = Self::wrap(node, self.shape.clone(), self.lazy.clone());
+        self.push_unary(self.shape.clone());
+        out
+    }
+}
+
+impl<P> TensorOpsMatmul<P, 2> for GraphTensor<P, 2>
+where
+    P: Copy + Default + std::fmt::Debug + std::ops::Add<Output = P> + std::ops::Mul<Output = P> + 'static,
+{
+    //matmul doesn't go through the lazy graph - it isn't elementwise, so
+    //TensorOpsDescription has nowhere to put it (see OpKind/is_elementwise)
+    fn matmul(&self, other: &Self) -> Self {
+        let out_value = self
+            .node
+            .borrow()
+            .value()
+            .matmul(&other.node.borrow().value())
+            .unwrap_or_else(|err| panic!("GraphTensor::matmul: {:?}", err));
+        let out_shape = out_value.shape.clone();
+        let node = record_matmul(self.node.clone(), other.node.clone())
+            .unwrap_or_else(|err| panic!("GraphTensor::matmul: {:?}", err));
+        Self::wrap(node, out_shape, self.lazy.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_broadcasts_mismatched_shapes() {
+        let lhs_value = Data::new(vec![1, 2, 3], Shape::new([1, 3]));
+        let lhs_shape = lhs_value.shape.clone();
+        let lhs = GraphTensor::fresh(crate::node_init!(root lhs_value), lhs_shape);
+
+        let rhs_value = Data::new(vec![10, 20], Shape::new([2, 1]));
+        let rhs_shape = rhs_value.shape.clone();
+        let rhs = GraphTensor::fresh(crate::node_init!(root rhs_value), rhs_shape);
+
+        let out = TensorOpsAdd::add(&lhs, &rhs).into_data();
+
+        assert_eq!(out.shape, Shape::new([2, 3]));
+        assert_eq!(out.value, vec![11, 12, 13, 21, 22, 23]);
+    }
+
+    #[test]
+    fn mul_broadcasts_mismatched_shapes() {
+        let lhs_value = Data::new(vec![1, 2, 3], Shape::new([1, 3]));
+        let lhs_shape = lhs_value.shape.clone();
+        let lhs = GraphTensor::fresh(crate::node_init!(root lhs_value), lhs_shape);
+
+        let rhs_value = Data::new(vec![10, 20], Shape::new([2, 1]));
+        let rhs_shape = rhs_value.shape.clone();
+        let rhs = GraphTensor::fresh(crate::node_init!(root rhs_value), rhs_shape);
+
+        let out = TensorOpsMul::mul(&lhs, &rhs).into_data();
+
+        assert_eq!(out.shape, Shape::new([2, 3]));
+        assert_eq!(out.value, vec![10, 20, 30, 20, 40, 60]);
+    }
+}
@@ -1,5 +1,7 @@
 use std::ops::Range;
 
+use derive_new::new;
+
 #[derive(new, Debug, Clone, PartialEq)]
 pub struct Shape<const D: usize> {
     pub dims: [usize; D],
@@ -16,6 +18,72 @@ impl<const D: usize> Shape<D> {
     }
 }
 
+impl<const D: usize> Shape<D> {
+    //numpy/dfdx-style broadcasting: both shapes already have the same rank
+    //here, so axes line up one-to-one rather than from the trailing
+    //dimension outward - an axis of size 1 on either side stretches to
+    //match the other, anything else is a mismatch
+    pub fn broadcast(&self, other: &Shape<D>) -> Result<Shape<D>, crate::tensor::tensor::TensorError> {
+        let mut dims = [0usize; D];
+
+        for (i, dim) in dims.iter_mut().enumerate() {
+            *dim = match (self.dims[i], other.dims[i]) {
+                (a, b) if a == b => a,
+                (1, b) => b,
+                (a, 1) => a,
+                (a, b) => {
+                    return Err(crate::tensor::tensor::TensorError::BroadcastError(format!(
+                        "cannot broadcast axis {}: {} vs {}",
+                        i, a, b
+                    )))
+                }
+            };
+        }
+
+        Ok(Shape::new(dims))
+    }
+
+    //stride for each axis, row-major, with size-1 axes forced to stride 0 -
+    //indexing with these strides reads the same element for every position
+    //along a size-1 axis, which is what "stretching" it actually means
+    pub(crate) fn broadcast_strides(&self) -> [usize; D] {
+        let mut strides = [0usize; D];
+        let mut acc = 1;
+
+        for i in (0..D).rev() {
+            strides[i] = if self.dims[i] == 1 { 0 } else { acc };
+            acc *= self.dims[i];
+        }
+
+        strides
+    }
+
+    //decomposes a flat, row-major index into this shape's per-axis indices
+    pub(crate) fn unravel(&self, flat: usize) -> [usize; D] {
+        let mut idx = [0usize; D];
+        let mut rem = flat;
+
+        for i in (0..D).rev() {
+            idx[i] = rem % self.dims[i];
+            rem /= self.dims[i];
+        }
+
+        idx
+    }
+
+    //flattens a multi-index taken from a (possibly larger, broadcasted)
+    //output shape down to this shape's own buffer offset, via its
+    //broadcast strides - any axis this shape stretched reads back to the
+    //same element regardless of the output index along that axis
+    pub(crate) fn ravel_broadcast(&self, idx: &[usize; D]) -> usize {
+        self.broadcast_strides()
+            .iter()
+            .zip(idx.iter())
+            .map(|(stride, i)| stride * i)
+            .sum()
+    }
+}
+
 impl<const D1: usize> Shape<D1> {
     pub fn index<const D2: usize>(&self, indexes: [Range<usize>; D2]) -> Self {
         if D2 > D1 {
@@ -33,9 +101,7 @@ impl<const D1: usize> Shape<D1> {
         //for the axes we didnt slice(i.e. D2 to D1) keep the original size
         //self.dims[i] is the original tensor's axis sizes
         //dims[i] is the new tensor's axis sizes
-        for i in D2..D1 {
-            dims[i] = self.dims[i];
-        }
+        dims[D2..D1].copy_from_slice(&self.dims[D2..D1]);
 
         Self::new(dims)
     }
@@ -2,8 +2,11 @@ use std::ops::Range;
 
 use crate::{Data, Shape};
 
+#[derive(Debug)]
 pub enum TensorError {
     ReshapeError(String),
+    MatmulError(String),
+    BroadcastError(String),
 }
 
 pub trait FloatTensor<P: num_traits::Float, const D:usize>:
@@ -15,24 +18,34 @@ pub trait FloatTensor<P: num_traits::Float, const D:usize>:
 pub trait TensorBase<P, const D: usize> {
     //self here is the instance of the type  that implements this trait
     //define a method called shape that takes a reference to self and returns a reference to a Shape with D dimensions
-    fn shape(&self) -> &Shape<D>
+    fn shape(&self) -> &Shape<D>;
     fn into_data(self) -> Data<P, D>;
     fn from <O: TensorBase<P, D>>(other: O) -> Self;
     fn empty(shape: Shape<D>) -> Self;
 }
 
 //create a public Trait with two generic parameters
+//forward impls of add/mul/neg below are expected to build their node via
+//graph::ops::record_add/record_mul/record_neg, which wrap the raw value in
+//a BinaryOpsNode/SingleOpsNode (see graph::ops) so the result differentiates.
+//operands of mismatched (but broadcast-compatible) shape should instead go
+//through record_broadcast_add/record_broadcast_mul, which sum gradients
+//back down to each parent's original shape on the way back.
 pub trait TensorOpsAdd<P, const D: usize>:
     //this trait requires that any implementing type must also implement the standard libary's add trait for self+self operations
     //and the implementing type must also implement the add trait for Self + P operations(P being a scalar getting added to the tensor)
-    std::ops::Add<Self, Output = Self> + sd::ops::Add<P, Output = Self>
+    std::ops::Add<Self, Output = Self> + std::ops::Add<P, Output = Self>
 where
     //with an additional constratint that implementing type must be sized(have a known size at compile time)
+    Self: Sized,
 {
     fn add(&self, other: &Self)-> Self;
     fn add_scalar(&self, other: &P) -> Self;
 }
 
+//forward impl is expected to build its node via graph::ops::record_matmul,
+//which contracts via Data::matmul and records a BinaryOpsNode<_, _, _, MatmulOp>
+//so the result differentiates with the standard matmul gradient rule
 pub trait TensorOpsMatmul<P, const D: usize> {
     fn matmul(&self, other: &Self) -> Self;
 }